@@ -0,0 +1,244 @@
+// Copyright 2012-2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Base16 (hex) binary-to-text encoding
+
+
+use std::vec;
+
+static LOWER_CHARS: [char, ..16] = [
+    '0', '1', '2', '3', '4', '5', '6', '7',
+    '8', '9', 'a', 'b', 'c', 'd', 'e', 'f'
+];
+
+static UPPER_CHARS: [char, ..16] = [
+    '0', '1', '2', '3', '4', '5', '6', '7',
+    '8', '9', 'A', 'B', 'C', 'D', 'E', 'F'
+];
+
+/// A trait for converting a value to hexadecimal encoding.
+pub trait ToHex {
+    /// Converts the value of `self` to a lowercase hex value, returning
+    /// the owned string.
+    fn to_hex(&self) -> ~str;
+
+    /// Converts the value of `self` to a hex value using uppercase
+    /// digits if `upper` is true, returning the owned string.
+    fn to_hex_case(&self, upper: bool) -> ~str;
+}
+
+impl<'self> ToHex for &'self [u8] {
+    /**
+     * Turn a vector of `u8` bytes into a hexadecimal string.
+     *
+     * # Example
+     *
+     * ~~~ {.rust}
+     * extern mod extra;
+     * use extra::base16::ToHex;
+     *
+     * fn main () {
+     *     let str = [52,32].to_hex();
+     *     println(fmt!("%s", str));
+     * }
+     * ~~~
+     */
+    fn to_hex(&self) -> ~str {
+        self.to_hex_case(false)
+    }
+
+    fn to_hex_case(&self, upper: bool) -> ~str {
+        let chars = if upper { &UPPER_CHARS } else { &LOWER_CHARS };
+        let mut s = ~"";
+        s.reserve(self.len() * 2u);
+
+        for &byte in self.iter() {
+            s.push_char(chars[(byte >> 4u) as uint]);
+            s.push_char(chars[(byte & 0x0Fu8) as uint]);
+        }
+        s
+    }
+}
+
+impl<'self> ToHex for &'self str {
+    /**
+     * Convert any string (literal, `@`, `&`, or `~`) to hexadecimal
+     * encoding.
+     *
+     * # Example
+     *
+     * ~~~ {.rust}
+     * extern mod extra;
+     * use extra::base16::ToHex;
+     *
+     * fn main () {
+     *     let str = "Hello, World".to_hex();
+     *     println(fmt!("%s",str));
+     * }
+     * ~~~
+     */
+    fn to_hex(&self) -> ~str {
+        self.as_bytes().to_hex()
+    }
+
+    fn to_hex_case(&self, upper: bool) -> ~str {
+        self.as_bytes().to_hex_case(upper)
+    }
+}
+
+/// Errors that can occur when decoding a hex encoded string.
+#[deriving(Eq)]
+pub enum FromHexError {
+    /// The input had a length that is not a multiple of two.
+    InvalidHexLength,
+    /// The input contained a character that is not a valid hex digit, at
+    /// the given byte offset.
+    InvalidHexCharacter(char, uint)
+}
+
+impl ToStr for FromHexError {
+    fn to_str(&self) -> ~str {
+        match *self {
+            InvalidHexLength =>
+                ~"invalid hex length",
+            InvalidHexCharacter(ch, idx) =>
+                fmt!("invalid hex character %? at byte %u", ch, idx)
+        }
+    }
+}
+
+#[allow(missing_doc)]
+pub trait FromHex {
+    fn from_hex(&self) -> Result<~[u8], FromHexError>;
+}
+
+impl<'self> FromHex for &'self [u8] {
+    /**
+     * Convert hex `u8` vector into u8 byte values. Every 2 hex digits is
+     * converted into 1 octet.
+     *
+     * # Example
+     *
+     * ~~~ {.rust}
+     * extern mod extra;
+     * use extra::base16::{ToHex, FromHex};
+     *
+     * fn main () {
+     *     let str = [52,32].to_hex();
+     *     println(fmt!("%s", str));
+     *     let bytes = str.from_hex().unwrap();
+     *     println(fmt!("%?",bytes));
+     * }
+     * ~~~
+     */
+    fn from_hex(&self) -> Result<~[u8], FromHexError> {
+        let len = self.len();
+        if len % 2u != 0u { return Err(InvalidHexLength); }
+
+        let mut r = vec::with_capacity(len / 2u);
+
+        let mut i = 0u;
+        while i < len {
+            let mut n = 0u;
+
+            for 2u.times {
+                let ch = self[i] as char;
+                n <<= 4u;
+
+                match ch {
+                    '0'..'9' => n |= (ch as uint) - ('0' as uint),
+                    'a'..'f' => n |= (ch as uint) - ('a' as uint) + 10u,
+                    'A'..'F' => n |= (ch as uint) - ('A' as uint) + 10u,
+                    _ => return Err(InvalidHexCharacter(ch, i))
+                }
+
+                i += 1u;
+            }
+
+            r.push(n as u8);
+        }
+        Ok(r)
+    }
+}
+
+impl<'self> FromHex for &'self str {
+    /**
+     * Convert any hex encoded string (literal, `@`, `&`, or `~`) to the
+     * byte values it encodes.
+     *
+     * You can use the `from_bytes` function in `std::str` to turn a
+     * `[u8]` into a string with characters corresponding to those
+     * values.
+     *
+     * # Example
+     *
+     * This converts a string literal to hexadecimal and back.
+     *
+     * ~~~ {.rust}
+     * extern mod extra;
+     * use extra::base16::{ToHex, FromHex};
+     * use std::str;
+     *
+     * fn main () {
+     *     let hello_str = "Hello, World".to_hex();
+     *     println(fmt!("%s",hello_str));
+     *     let bytes = hello_str.from_hex().unwrap();
+     *     println(fmt!("%?",bytes));
+     *     let result_str = str::from_bytes(bytes);
+     *     println(fmt!("%s",result_str));
+     * }
+     * ~~~
+     */
+    fn from_hex(&self) -> Result<~[u8], FromHexError> {
+        self.as_bytes().from_hex()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidHexLength, InvalidHexCharacter};
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!("".to_hex(), ~"");
+        assert_eq!("f".to_hex(), ~"66");
+        assert_eq!("foobar".to_hex(), ~"666f6f626172");
+    }
+
+    #[test]
+    fn test_to_hex_case() {
+        assert_eq!("foobar".to_hex_case(false), ~"666f6f626172");
+        assert_eq!("foobar".to_hex_case(true), ~"666F6F626172");
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!("".from_hex().unwrap(), "".as_bytes().to_owned());
+        assert_eq!("66".from_hex().unwrap(), "f".as_bytes().to_owned());
+        assert_eq!("666f6f626172".from_hex().unwrap(), "foobar".as_bytes().to_owned());
+        assert_eq!("666F6F626172".from_hex().unwrap(), "foobar".as_bytes().to_owned());
+    }
+
+    #[test]
+    fn test_from_hex_invalid_length() {
+        match "666".from_hex() {
+            Err(InvalidHexLength) => (),
+            _ => fail!("expected InvalidHexLength")
+        }
+    }
+
+    #[test]
+    fn test_from_hex_invalid_character() {
+        match "6g".from_hex() {
+            Err(InvalidHexCharacter('g', 1)) => (),
+            _ => fail!("expected InvalidHexCharacter")
+        }
+    }
+}
@@ -12,15 +12,60 @@
 
 
 use std::vec;
+use std::io::{Reader, Writer};
 
-/// A trait for converting a value to base64 encoding.
-pub trait ToBase64 {
-    /// Converts the value of `self` to a base64 value, returning the owned
-    /// string
-    fn to_base64(&self) -> ~str;
+/// Contains configuration parameters for `to_base64`.
+#[deriving(Clone, Eq)]
+pub struct Config {
+    /// Character set to use
+    char_set: CharacterSet,
+    /// True to pad output with `=` characters
+    pad: bool,
+    /// `Some(len)` to wrap the encoded output into lines of `len`
+    /// characters (not counting the line separator itself), or `None`
+    /// to emit the whole string on one line.
+    line_length: Option<uint>,
+    /// Which line separator to use when `line_length` is set.
+    newline: Newline
+}
+
+/// Available character sets for base64 encoding and decoding.
+#[deriving(Clone, Eq)]
+pub enum CharacterSet {
+    /// The standard character set (uses `+` and `/`)
+    Standard,
+    /// The URL safe character set (uses `-` and `_`)
+    UrlSafe
+}
+
+/// Line separator styles for wrapped base64 output.
+#[deriving(Clone, Eq)]
+pub enum Newline {
+    /// A carriage return followed by a line feed (`\r\n`), as used in
+    /// MIME and PEM.
+    CRLF,
+    /// A bare line feed (`\n`).
+    LF
 }
 
-static CHARS: [char, ..64] = [
+/// Standard character set, with padding.
+pub static STANDARD: Config =
+    Config {char_set: Standard, pad: true, line_length: None, newline: LF};
+
+/// URL safe character set, with padding.
+pub static URL_SAFE: Config =
+    Config {char_set: UrlSafe, pad: true, line_length: None, newline: LF};
+
+/// URL safe character set, without padding.
+pub static URL_SAFE_NO_PAD: Config =
+    Config {char_set: UrlSafe, pad: false, line_length: None, newline: LF};
+
+/// Standard character set, wrapped into 76-character lines separated by
+/// `\r\n`, as used by MIME and PEM.
+pub static MIME: Config =
+    Config {char_set: Standard, pad: true, line_length: Some(76), newline: CRLF};
+
+static STANDARD_CHARS: [char, ..64] = [
     'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
     'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm',
@@ -28,6 +73,46 @@ static CHARS: [char, ..64] = [
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '/'
 ];
 
+static URL_SAFE_CHARS: [char, ..64] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
+    'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm',
+    'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '-', '_'
+];
+
+fn chars_for(char_set: CharacterSet) -> &'static [char, ..64] {
+    match char_set {
+        Standard => &STANDARD_CHARS,
+        UrlSafe => &URL_SAFE_CHARS
+    }
+}
+
+/// Appends `ch` to `s`, inserting a line separator first whenever `col`
+/// (the current column on the line being built) reaches the configured
+/// line length.
+fn push_wrapped(s: &mut ~str, col: &mut uint, ch: char, config: Config) {
+    match config.line_length {
+        Some(line_length) if *col >= line_length => {
+            match config.newline {
+                CRLF => s.push_str("\r\n"),
+                LF => s.push_char('\n')
+            }
+            *col = 0u;
+        }
+        _ => ()
+    }
+    s.push_char(ch);
+    *col += 1u;
+}
+
+/// A trait for converting a value to base64 encoding.
+pub trait ToBase64 {
+    /// Converts the value of `self` to a base64 value following the
+    /// specified format configuration, returning the owned string.
+    fn to_base64(&self, config: Config) -> ~str;
+}
+
 impl<'self> ToBase64 for &'self [u8] {
     /**
      * Turn a vector of `u8` bytes into a base64 string.
@@ -36,20 +121,22 @@ impl<'self> ToBase64 for &'self [u8] {
      *
      * ~~~ {.rust}
      * extern mod extra;
-     * use extra::base64::ToBase64;
+     * use extra::base64::{ToBase64, STANDARD};
      *
      * fn main () {
-     *     let str = [52,32].to_base64();
+     *     let str = [52,32].to_base64(STANDARD);
      *     println(fmt!("%s", str));
      * }
      * ~~~
      */
-    fn to_base64(&self) -> ~str {
+    fn to_base64(&self, config: Config) -> ~str {
+        let chars = chars_for(config.char_set);
         let mut s = ~"";
         let len = self.len();
         s.reserve(((len + 3u) / 4u) * 3u);
 
         let mut i = 0u;
+        let mut col = 0u;
 
         while i < len - (len % 3u) {
             let n = (self[i] as uint) << 16u |
@@ -57,10 +144,10 @@ impl<'self> ToBase64 for &'self [u8] {
                     (self[i + 2u] as uint);
 
             // This 24-bit number gets separated into four 6-bit numbers.
-            s.push_char(CHARS[(n >> 18u) & 63u]);
-            s.push_char(CHARS[(n >> 12u) & 63u]);
-            s.push_char(CHARS[(n >> 6u) & 63u]);
-            s.push_char(CHARS[n & 63u]);
+            push_wrapped(&mut s, &mut col, chars[(n >> 18u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[(n >> 12u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[(n >> 6u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[n & 63u], config);
 
             i += 3u;
         }
@@ -71,18 +158,22 @@ impl<'self> ToBase64 for &'self [u8] {
           0 => (),
           1 => {
             let n = (self[i] as uint) << 16u;
-            s.push_char(CHARS[(n >> 18u) & 63u]);
-            s.push_char(CHARS[(n >> 12u) & 63u]);
-            s.push_char('=');
-            s.push_char('=');
+            push_wrapped(&mut s, &mut col, chars[(n >> 18u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[(n >> 12u) & 63u], config);
+            if config.pad {
+                push_wrapped(&mut s, &mut col, '=', config);
+                push_wrapped(&mut s, &mut col, '=', config);
+            }
           }
           2 => {
             let n = (self[i] as uint) << 16u |
                 (self[i + 1u] as uint) << 8u;
-            s.push_char(CHARS[(n >> 18u) & 63u]);
-            s.push_char(CHARS[(n >> 12u) & 63u]);
-            s.push_char(CHARS[(n >> 6u) & 63u]);
-            s.push_char('=');
+            push_wrapped(&mut s, &mut col, chars[(n >> 18u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[(n >> 12u) & 63u], config);
+            push_wrapped(&mut s, &mut col, chars[(n >> 6u) & 63u], config);
+            if config.pad {
+                push_wrapped(&mut s, &mut col, '=', config);
+            }
           }
           _ => fail!("Algebra is broken, please alert the math police")
         }
@@ -99,23 +190,48 @@ impl<'self> ToBase64 for &'self str {
      *
      * ~~~ {.rust}
      * extern mod extra;
-     * use extra::base64::ToBase64;
+     * use extra::base64::{ToBase64, STANDARD};
      *
      * fn main () {
-     *     let str = "Hello, World".to_base64();
+     *     let str = "Hello, World".to_base64(STANDARD);
      *     println(fmt!("%s",str));
      * }
      * ~~~
      *
      */
-    fn to_base64(&self) -> ~str {
-        self.as_bytes().to_base64()
+    fn to_base64(&self, config: Config) -> ~str {
+        self.as_bytes().to_base64(config)
+    }
+}
+
+/// Errors that can occur when decoding a base64 encoded string.
+#[deriving(Clone, Eq)]
+pub enum FromBase64Error {
+    /// The input had a length that is not a valid base64 length.
+    InvalidBase64Length,
+    /// The input contained a character not allowed in the chosen
+    /// character set, at the given byte offset.
+    InvalidBase64Character(char, uint),
+    /// The input had padding (`=`) in a position that is not valid.
+    InvalidBase64Padding
+}
+
+impl ToStr for FromBase64Error {
+    fn to_str(&self) -> ~str {
+        match *self {
+            InvalidBase64Length =>
+                ~"invalid base64 length",
+            InvalidBase64Character(ch, idx) =>
+                fmt!("invalid base64 character %? at byte %u", ch, idx),
+            InvalidBase64Padding =>
+                ~"invalid base64 padding"
+        }
     }
 }
 
 #[allow(missing_doc)]
 pub trait FromBase64 {
-    fn from_base64(&self) -> ~[u8];
+    fn from_base64(&self, config: Config) -> Result<~[u8], FromBase64Error>;
 }
 
 impl<'self> FromBase64 for &'self [u8] {
@@ -127,69 +243,128 @@ impl<'self> FromBase64 for &'self [u8] {
      *
      * ~~~ {.rust}
      * extern mod extra;
-     * use extra::base64::ToBase64;
-     * use extra::base64::FromBase64;
+     * use extra::base64::{ToBase64, FromBase64, STANDARD};
      *
      * fn main () {
-     *     let str = [52,32].to_base64();
+     *     let str = [52,32].to_base64(STANDARD);
      *     println(fmt!("%s", str));
-     *     let bytes = str.from_base64();
+     *     let bytes = str.from_base64(STANDARD).unwrap();
      *     println(fmt!("%?",bytes));
      * }
      * ~~~
      */
-    fn from_base64(&self) -> ~[u8] {
-        if self.len() % 4u != 0u { fail!("invalid base64 length"); }
+    fn from_base64(&self, config: Config) -> Result<~[u8], FromBase64Error> {
+        // Lines wrapped for MIME/PEM carry `\r\n` (or bare `\n`) between
+        // groups of four characters; strip spaces, tabs, `\r` and `\n` up front
+        // so the `len % 4` invariant below only sees significant characters.
+        // Each surviving byte keeps the index it had in the caller's
+        // original input, so errors report an offset the caller can
+        // actually locate, not one shifted by however much whitespace was
+        // skipped ahead of it.
+        let this: ~[(u8, uint)] = self.iter().enumerate().filter_map(|(idx, &b)| {
+            match b {
+                b' ' | b'\t' | b'\r' | b'\n' => None,
+                _ => Some((b, idx))
+            }
+        }).collect();
 
-        let len = self.len();
-        let mut padding = 0u;
+        let len = this.len();
 
-        if len != 0u {
-            if self[len - 1u] == '=' as u8 { padding += 1u; }
-            if self[len - 2u] == '=' as u8 { padding += 1u; }
+        if config.pad {
+            if len % 4u != 0u { return Err(InvalidBase64Length); }
         }
 
-        let mut r = vec::with_capacity((len / 4u) * 3u - padding);
+        let mut r = vec::with_capacity((len / 4u + 1u) * 3u);
 
         let mut i = 0u;
         while i < len {
             let mut n = 0u;
+            let mut sextets = 0u;
 
             for 4u.times {
-                let ch = self[i] as char;
+                if i >= len {
+                    break;
+                }
+
+                let (byte, orig_idx) = this[i];
+                let ch = byte as char;
                 n <<= 6u;
 
                 match ch {
-                    'A'..'Z' => n |= (ch as uint) - 0x41,
-                    'a'..'z' => n |= (ch as uint) - 0x47,
-                    '0'..'9' => n |= (ch as uint) + 0x04,
-                    '+'      => n |= 0x3E,
-                    '/'      => n |= 0x3F,
+                    'A'..'Z' => { n |= (ch as uint) - 0x41; sextets += 1u; }
+                    'a'..'z' => { n |= (ch as uint) - 0x47; sextets += 1u; }
+                    '0'..'9' => { n |= (ch as uint) + 0x04; sextets += 1u; }
+                    '+'      => {
+                        if config.char_set == UrlSafe {
+                            return Err(InvalidBase64Character(ch, orig_idx));
+                        }
+                        n |= 0x3E;
+                        sextets += 1u;
+                    }
+                    '/'      => {
+                        if config.char_set == UrlSafe {
+                            return Err(InvalidBase64Character(ch, orig_idx));
+                        }
+                        n |= 0x3F;
+                        sextets += 1u;
+                    }
+                    '-'      => {
+                        if config.char_set != UrlSafe {
+                            return Err(InvalidBase64Character(ch, orig_idx));
+                        }
+                        n |= 0x3E;
+                        sextets += 1u;
+                    }
+                    '_'      => {
+                        if config.char_set != UrlSafe {
+                            return Err(InvalidBase64Character(ch, orig_idx));
+                        }
+                        n |= 0x3F;
+                        sextets += 1u;
+                    }
                     '='      => {
                         match len - i {
                             1u => {
                                 r.push(((n >> 16u) & 0xFFu) as u8);
                                 r.push(((n >> 8u ) & 0xFFu) as u8);
-                                return copy r;
+                                return Ok(copy r);
                             }
                             2u => {
                                 r.push(((n >> 10u) & 0xFFu) as u8);
-                                return copy r;
+                                return Ok(copy r);
                             }
-                            _ => fail!("invalid base64 padding")
+                            _ => return Err(InvalidBase64Padding)
                         }
                     }
-                    _ => fail!("invalid base64 character")
+                    _ => return Err(InvalidBase64Character(ch, orig_idx))
                 }
 
                 i += 1u;
             };
 
-            r.push(((n >> 16u) & 0xFFu) as u8);
-            r.push(((n >> 8u ) & 0xFFu) as u8);
-            r.push(((n       ) & 0xFFu) as u8);
+            // Final, possibly partial, group when padding is disabled: shift
+            // the accumulated sextets into place and emit only the bytes
+            // that a full group of that size would have contributed.
+            n <<= (4u - sextets) * 6u;
+
+            match sextets {
+                4u => {
+                    r.push(((n >> 16u) & 0xFFu) as u8);
+                    r.push(((n >> 8u ) & 0xFFu) as u8);
+                    r.push(((n       ) & 0xFFu) as u8);
+                }
+                3u => {
+                    r.push(((n >> 16u) & 0xFFu) as u8);
+                    r.push(((n >> 8u ) & 0xFFu) as u8);
+                }
+                2u => {
+                    r.push(((n >> 16u) & 0xFFu) as u8);
+                }
+                0u => (),
+                _ => return Err(InvalidBase64Length)
+            }
         }
-        r
+        Ok(r)
     }
 }
 
@@ -207,46 +382,362 @@ impl<'self> FromBase64 for &'self str {
      *
      * ~~~ {.rust}
      * extern mod extra;
-     * use extra::base64::ToBase64;
-     * use extra::base64::FromBase64;
+     * use extra::base64::{ToBase64, FromBase64, STANDARD};
      * use std::str;
      *
      * fn main () {
-     *     let hello_str = "Hello, World".to_base64();
+     *     let hello_str = "Hello, World".to_base64(STANDARD);
      *     println(fmt!("%s",hello_str));
-     *     let bytes = hello_str.from_base64();
+     *     let bytes = hello_str.from_base64(STANDARD).unwrap();
      *     println(fmt!("%?",bytes));
      *     let result_str = str::from_bytes(bytes);
      *     println(fmt!("%s",result_str));
      * }
      * ~~~
      */
-    fn from_base64(&self) -> ~[u8] {
-        self.as_bytes().from_base64()
+    fn from_base64(&self, config: Config) -> Result<~[u8], FromBase64Error> {
+        self.as_bytes().from_base64(config)
+    }
+}
+
+/// Wraps a `Writer`, base64-encoding bytes written to it before passing
+/// the encoded text on to the underlying writer.
+///
+/// Input is buffered in groups of three bytes so that a full quad can be
+/// emitted as soon as it is available; call `finish` once all input has
+/// been written to flush any partial group (padded per `config`).
+pub struct Base64Writer<W> {
+    priv writer: W,
+    priv config: Config,
+    priv buf: [u8, ..3],
+    priv buf_len: uint,
+    priv col: uint
+}
+
+impl<W: Writer> Base64Writer<W> {
+    /// Creates a new `Base64Writer` that encodes with `config` and writes
+    /// the result to `writer`.
+    pub fn new(writer: W, config: Config) -> Base64Writer<W> {
+        Base64Writer {
+            writer: writer,
+            config: config,
+            buf: [0u8, ..3],
+            buf_len: 0u,
+            col: 0u
+        }
+    }
+
+    fn emit_char(&mut self, ch: char) {
+        match self.config.line_length {
+            Some(line_length) if self.col >= line_length => {
+                match self.config.newline {
+                    CRLF => self.writer.write("\r\n".as_bytes()),
+                    LF => self.writer.write("\n".as_bytes())
+                }
+                self.col = 0u;
+            }
+            _ => ()
+        }
+        self.writer.write(&[ch as u8]);
+        self.col += 1u;
+    }
+
+    /// Encodes and emits the three buffered bytes as a full quad.
+    fn emit_full_group(&mut self) {
+        let chars = chars_for(self.config.char_set);
+        let n = (self.buf[0] as uint) << 16u |
+                (self.buf[1] as uint) << 8u |
+                (self.buf[2] as uint);
+
+        self.emit_char(chars[(n >> 18u) & 63u]);
+        self.emit_char(chars[(n >> 12u) & 63u]);
+        self.emit_char(chars[(n >> 6u) & 63u]);
+        self.emit_char(chars[n & 63u]);
+        self.buf_len = 0u;
+    }
+
+    /// Flushes the final, possibly partial, group, padding it according
+    /// to `config`. No more bytes should be written afterwards.
+    pub fn finish(&mut self) {
+        let chars = chars_for(self.config.char_set);
+
+        match self.buf_len {
+            0u => (),
+            1u => {
+                let n = (self.buf[0] as uint) << 16u;
+                self.emit_char(chars[(n >> 18u) & 63u]);
+                self.emit_char(chars[(n >> 12u) & 63u]);
+                if self.config.pad {
+                    self.emit_char('=');
+                    self.emit_char('=');
+                }
+            }
+            2u => {
+                let n = (self.buf[0] as uint) << 16u |
+                        (self.buf[1] as uint) << 8u;
+                self.emit_char(chars[(n >> 18u) & 63u]);
+                self.emit_char(chars[(n >> 12u) & 63u]);
+                self.emit_char(chars[(n >> 6u) & 63u]);
+                if self.config.pad {
+                    self.emit_char('=');
+                }
+            }
+            _ => fail!("Algebra is broken, please alert the math police")
+        }
+        self.buf_len = 0u;
+    }
+}
+
+impl<W: Writer> Writer for Base64Writer<W> {
+    /// Encodes and writes `buf`, buffering any bytes left over until the
+    /// next three-byte group is complete.
+    fn write(&mut self, buf: &[u8]) {
+        for &byte in buf.iter() {
+            self.buf[self.buf_len] = byte;
+            self.buf_len += 1u;
+            if self.buf_len == 3u {
+                self.emit_full_group();
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.writer.flush();
+    }
+}
+
+/// Wraps a `Reader`, decoding base64 text read from it into raw bytes.
+///
+/// Decoding happens on the fly: encoded quads are read and converted one
+/// at a time, with any leftover decoded bytes buffered across calls to
+/// `read`.
+pub struct Base64Reader<R> {
+    priv reader: R,
+    priv config: Config,
+    priv pending: ~[u8],
+    priv pending_pos: uint,
+    /// Set once a decode error is hit; `read` reports it as EOF from then
+    /// on and callers can recover the real cause via `error()`.
+    priv error: Option<FromBase64Error>
+}
+
+impl<R: Reader> Base64Reader<R> {
+    /// Creates a new `Base64Reader` that decodes with `config` from
+    /// `reader`.
+    pub fn new(reader: R, config: Config) -> Base64Reader<R> {
+        Base64Reader {
+            reader: reader,
+            config: config,
+            pending: ~[],
+            pending_pos: 0u,
+            error: None
+        }
+    }
+
+    /// Returns the decode error that ended the stream early, if any.
+    /// `read` starts returning `None` as soon as this is set.
+    pub fn error(&self) -> Option<FromBase64Error> {
+        self.error.clone()
+    }
+
+    fn fill_pending(&mut self) {
+        let mut quad = [0u8, ..4];
+        let mut quad_len = 0u;
+
+        while quad_len < 4u {
+            let mut byte = [0u8, ..1];
+            match self.reader.read(&mut byte) {
+                Some(0u) | None => break,
+                Some(_) => {
+                    match byte[0] as char {
+                        ' ' | '\t' | '\r' | '\n' => (),
+                        _ => {
+                            quad[quad_len] = byte[0];
+                            quad_len += 1u;
+                        }
+                    }
+                }
+            }
+        }
+
+        if quad_len > 0u {
+            match quad.slice(0u, quad_len).from_base64(self.config) {
+                Ok(decoded) => {
+                    self.pending = decoded;
+                    self.pending_pos = 0u;
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Reader> Reader for Base64Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Option<uint> {
+        let mut n = 0u;
+
+        while n < buf.len() {
+            if self.pending_pos >= self.pending.len() {
+                if self.error.is_some() {
+                    break;
+                }
+                self.fill_pending();
+                if self.pending_pos >= self.pending.len() {
+                    // Either the underlying reader is at EOF, or the
+                    // quad we just read failed to decode; either way
+                    // there is nothing more to hand back.
+                    break;
+                }
+            }
+
+            while n < buf.len() && self.pending_pos < self.pending.len() {
+                buf[n] = self.pending[self.pending_pos];
+                self.pending_pos += 1u;
+                n += 1u;
+            }
+        }
+
+        if n == 0u { None } else { Some(n) }
+    }
+
+    fn eof(&mut self) -> bool {
+        self.pending_pos >= self.pending.len() &&
+            (self.error.is_some() || self.reader.eof())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD, MIME};
+    use super::{InvalidBase64Length, InvalidBase64Character, InvalidBase64Padding};
+    use super::{Base64Writer, Base64Reader};
+    use std::io::{Reader, Writer};
+    use std::io::mem::{MemWriter, MemReader};
+
+    #[test]
+    fn test_to_base64_standard() {
+        assert_eq!("".to_base64(STANDARD), ~"");
+        assert_eq!("f".to_base64(STANDARD), ~"Zg==");
+        assert_eq!("fo".to_base64(STANDARD), ~"Zm8=");
+        assert_eq!("foo".to_base64(STANDARD), ~"Zm9v");
+        assert_eq!("foob".to_base64(STANDARD), ~"Zm9vYg==");
+        assert_eq!("fooba".to_base64(STANDARD), ~"Zm9vYmE=");
+        assert_eq!("foobar".to_base64(STANDARD), ~"Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_from_base64_standard() {
+        assert_eq!("".from_base64(STANDARD).unwrap(), "".as_bytes().to_owned());
+        assert_eq!("Zg==".from_base64(STANDARD).unwrap(), "f".as_bytes().to_owned());
+        assert_eq!("Zm8=".from_base64(STANDARD).unwrap(), "fo".as_bytes().to_owned());
+        assert_eq!("Zm9v".from_base64(STANDARD).unwrap(), "foo".as_bytes().to_owned());
+        assert_eq!("Zm9vYg==".from_base64(STANDARD).unwrap(), "foob".as_bytes().to_owned());
+        assert_eq!("Zm9vYmE=".from_base64(STANDARD).unwrap(), "fooba".as_bytes().to_owned());
+        assert_eq!("Zm9vYmFy".from_base64(STANDARD).unwrap(), "foobar".as_bytes().to_owned());
+    }
+
+    #[test]
+    fn test_to_base64_url_safe() {
+        assert_eq!([251, 255].to_base64(URL_SAFE), ~"-_8=");
+        assert_eq!([251, 255].to_base64(STANDARD), ~"+/8=");
+    }
+
+    #[test]
+    fn test_base64_url_safe_no_pad_roundtrip() {
+        let bytes = [251, 255, 0, 127];
+        let encoded = bytes.to_base64(URL_SAFE_NO_PAD);
+        assert!(!encoded.contains_char('='));
+        assert_eq!(encoded.from_base64(URL_SAFE_NO_PAD).unwrap(), bytes.to_owned());
+    }
+
+    #[test]
+    fn test_from_base64_invalid_length() {
+        match "Zg".from_base64(STANDARD) {
+            Err(InvalidBase64Length) => (),
+            _ => fail!("expected InvalidBase64Length")
+        }
+    }
+
+    #[test]
+    fn test_from_base64_invalid_character() {
+        match "Z$==".from_base64(STANDARD) {
+            Err(InvalidBase64Character('$', 1)) => (),
+            _ => fail!("expected InvalidBase64Character")
+        }
+    }
+
+    #[test]
+    fn test_from_base64_invalid_padding() {
+        match "A=AA".from_base64(STANDARD) {
+            Err(InvalidBase64Padding) => (),
+            _ => fail!("expected InvalidBase64Padding")
+        }
+    }
+
+    #[test]
+    fn test_to_base64_mime_wraps_lines() {
+        let input = ['a' as u8, ..60];
+        let encoded = input.to_base64(MIME);
+        let lines: ~[&str] = encoded.split_str_iter("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 76);
+    }
+
+    #[test]
+    fn test_from_base64_whitespace_tolerant() {
+        let wrapped = "Zm9v\r\nYmFy\r\n";
+        assert_eq!(wrapped.from_base64(STANDARD).unwrap(), "foobar".as_bytes().to_owned());
+    }
+
+    #[test]
+    fn test_from_base64_invalid_character_offset_skips_whitespace() {
+        // The `$` sits at byte 3 of the original string; the whitespace
+        // bytes ahead of it must not shift the reported offset.
+        match "A \n$==".from_base64(STANDARD) {
+            Err(InvalidBase64Character('$', 3)) => (),
+            _ => fail!("expected InvalidBase64Character('$', 3)")
+        }
+    }
+
+    #[test]
+    fn test_base64_writer_roundtrips_with_to_base64() {
+        let mut w = Base64Writer::new(MemWriter::new(), STANDARD);
+        w.write("foobar".as_bytes());
+        w.finish();
+        assert_eq!(w.writer.get_ref(), "foobar".as_bytes().to_base64(STANDARD).as_bytes());
+    }
+
     #[test]
-    fn test_to_base64() {
-        assert_eq!("".to_base64(), ~"");
-        assert_eq!("f".to_base64(), ~"Zg==");
-        assert_eq!("fo".to_base64(), ~"Zm8=");
-        assert_eq!("foo".to_base64(), ~"Zm9v");
-        assert_eq!("foob".to_base64(), ~"Zm9vYg==");
-        assert_eq!("fooba".to_base64(), ~"Zm9vYmE=");
-        assert_eq!("foobar".to_base64(), ~"Zm9vYmFy");
+    fn test_base64_reader_roundtrips_with_from_base64() {
+        let encoded = "foobar".as_bytes().to_base64(STANDARD);
+        let mut r = Base64Reader::new(MemReader::new(encoded.into_bytes()), STANDARD);
+        let mut out = [0u8, ..6];
+        assert_eq!(r.read(&mut out), Some(6u));
+        assert_eq!(out.to_owned(), "foobar".as_bytes().to_owned());
     }
 
     #[test]
-    fn test_from_base64() {
-        assert_eq!("".from_base64(), "".as_bytes().to_owned());
-        assert_eq!("Zg==".from_base64(), "f".as_bytes().to_owned());
-        assert_eq!("Zm8=".from_base64(), "fo".as_bytes().to_owned());
-        assert_eq!("Zm9v".from_base64(), "foo".as_bytes().to_owned());
-        assert_eq!("Zm9vYg==".from_base64(), "foob".as_bytes().to_owned());
-        assert_eq!("Zm9vYmE=".from_base64(), "fooba".as_bytes().to_owned());
-        assert_eq!("Zm9vYmFy".from_base64(), "foobar".as_bytes().to_owned());
+    fn test_base64_reader_fills_buffer_across_multiple_quads() {
+        // "foobar" decodes from two quads ("Zm9v" -> "foo", "YmFy" ->
+        // "bar"); a buffer larger than one quad's output forces `read` to
+        // pull from more than one `fill_pending` call per invocation.
+        let encoded = "foobar".as_bytes().to_base64(STANDARD);
+        let mut r = Base64Reader::new(MemReader::new(encoded.into_bytes()), STANDARD);
+        let mut out = [0u8, ..4];
+        assert_eq!(r.read(&mut out), Some(4u));
+        assert_eq!(out.to_owned(), "foob".as_bytes().to_owned());
+    }
+
+    #[test]
+    fn test_base64_reader_surfaces_decode_error_instead_of_failing() {
+        let mut r = Base64Reader::new(MemReader::new("A$==".as_bytes().to_owned()), STANDARD);
+        let mut out = [0u8, ..3];
+        assert_eq!(r.read(&mut out), None);
+        match r.error() {
+            Some(InvalidBase64Character('$', 1)) => (),
+            _ => fail!("expected InvalidBase64Character('$', 1)")
+        }
     }
 }